@@ -3,12 +3,20 @@ use std::path::PathBuf;
 use html_escape::encode_text;
 use std::fmt;
 use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
 
 /// HTML attribute encoding function
 fn encode_attribute(s: &str) -> String {
     encode_text(s).to_string()
 }
 
+/// Escapes text content the same way `TextElement` does (base HTML
+/// escaping plus single quotes, per OWASP recommendations). Shared with
+/// `Content::Text` so both paths stay in sync.
+fn escape_text_content(s: &str) -> String {
+    encode_text(s).to_string().replace('\'', "&#x27;")
+}
+
 /// A trait representing any HTML element that can be rendered to a string
 pub trait HtmlElement: {
     /// Returns the HTML tag name for this element (e.g., "div", "span", "img")
@@ -38,7 +46,36 @@ pub trait HtmlElement: {
     fn render(&self) -> String where Self: Sized {
         render_element(self)
     }
-    
+
+    /// Renders the element, rejecting inputs that would otherwise
+    /// silently produce broken markup (a void element with children, an
+    /// empty or malformed tag name, an attribute name that can't appear
+    /// in HTML). Use this over `render()` whenever the tree wasn't built
+    /// entirely from this crate's own constructors.
+    ///
+    /// Named `render_checked` rather than `serialize` so it doesn't
+    /// collide with `serde::Serialize::serialize` on types (like
+    /// `ContainerElement`/`Content`) that derive both.
+    fn render_checked(&self) -> std::result::Result<String, HtmlError> where Self: Sized {
+        validate_and_render(self)
+    }
+
+    /// Renders the element with one nested element per line, indented
+    /// per `opts`, instead of `render()`'s single unbroken line. When all
+    /// of an element's children are inline markup (see `is_inline_tag`)
+    /// or text runs, they stay collapsed onto the element's line, so
+    /// phrasing content like a paragraph doesn't pick up spurious internal
+    /// whitespace. In an element that mixes inline and block children,
+    /// every child, inline ones included, goes on its own line.
+    fn render_pretty(&self, opts: PrettyOptions) -> String where Self: Sized {
+        let mut out = String::new();
+        render_pretty_block(self, 0, &opts, &mut out);
+        if out.ends_with('\n') {
+            out.pop();
+        }
+        out
+    }
+
     /// Helper to check if element has any content (text or children)
     fn has_content(&self) -> bool {
         self.text().is_some() || !self.children().is_empty()
@@ -69,11 +106,7 @@ impl HtmlElement for TextElement {
     }
 
     fn text(&self) -> Option<String> {
-        // 基础转义
-        let encoded = encode_text(&self.content).to_string();
-        // 额外处理单引号（根据 OWASP 推荐）
-        let full_encoded = encoded.replace('\'', "&#x27;");
-        Some(full_encoded)
+        Some(escape_text_content(&self.content))
     }
 
     fn is_void_element(&self) -> bool {
@@ -171,18 +204,300 @@ impl HtmlElement for ImageElement {
     }
 }
 
+/// The standard HTML5 tag vocabulary, plus an `Other` escape hatch for
+/// custom or non-standard tags.
+///
+/// Centralizing the tag list here means void-element status is derived
+/// from the tag itself (see [`ElementTag::is_void`]) instead of being
+/// hand-maintained by every `HtmlElement` implementor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ElementTag {
+    Html,
+    Head,
+    Body,
+    Title,
+    Meta,
+    Link,
+    Style,
+    Script,
+    Div,
+    Span,
+    P,
+    A,
+    Ul,
+    Ol,
+    Li,
+    Nav,
+    Header,
+    Footer,
+    Section,
+    Article,
+    H1,
+    H2,
+    H3,
+    H4,
+    H5,
+    H6,
+    Strong,
+    Em,
+    B,
+    I,
+    Code,
+    Pre,
+    Blockquote,
+    Table,
+    Thead,
+    Tbody,
+    Tr,
+    Td,
+    Th,
+    Form,
+    Label,
+    Button,
+    Img,
+    Br,
+    Hr,
+    Input,
+    /// Any tag not covered above, kept verbatim.
+    Other(String),
+}
+
+impl ElementTag {
+    /// Returns the lowercase tag name used when rendering, e.g. `"div"`.
+    pub fn name(&self) -> &str {
+        match self {
+            ElementTag::Html => "html",
+            ElementTag::Head => "head",
+            ElementTag::Body => "body",
+            ElementTag::Title => "title",
+            ElementTag::Meta => "meta",
+            ElementTag::Link => "link",
+            ElementTag::Style => "style",
+            ElementTag::Script => "script",
+            ElementTag::Div => "div",
+            ElementTag::Span => "span",
+            ElementTag::P => "p",
+            ElementTag::A => "a",
+            ElementTag::Ul => "ul",
+            ElementTag::Ol => "ol",
+            ElementTag::Li => "li",
+            ElementTag::Nav => "nav",
+            ElementTag::Header => "header",
+            ElementTag::Footer => "footer",
+            ElementTag::Section => "section",
+            ElementTag::Article => "article",
+            ElementTag::H1 => "h1",
+            ElementTag::H2 => "h2",
+            ElementTag::H3 => "h3",
+            ElementTag::H4 => "h4",
+            ElementTag::H5 => "h5",
+            ElementTag::H6 => "h6",
+            ElementTag::Strong => "strong",
+            ElementTag::Em => "em",
+            ElementTag::B => "b",
+            ElementTag::I => "i",
+            ElementTag::Code => "code",
+            ElementTag::Pre => "pre",
+            ElementTag::Blockquote => "blockquote",
+            ElementTag::Table => "table",
+            ElementTag::Thead => "thead",
+            ElementTag::Tbody => "tbody",
+            ElementTag::Tr => "tr",
+            ElementTag::Td => "td",
+            ElementTag::Th => "th",
+            ElementTag::Form => "form",
+            ElementTag::Label => "label",
+            ElementTag::Button => "button",
+            ElementTag::Img => "img",
+            ElementTag::Br => "br",
+            ElementTag::Hr => "hr",
+            ElementTag::Input => "input",
+            ElementTag::Other(name) => name,
+        }
+    }
+
+    /// Returns true for elements that have no closing tag and no
+    /// children, e.g. `<img/>` or `<br/>`.
+    pub fn is_void(&self) -> bool {
+        matches!(
+            self,
+            ElementTag::Img | ElementTag::Br | ElementTag::Hr | ElementTag::Input | ElementTag::Meta | ElementTag::Link
+        )
+    }
+}
+
+impl From<&str> for ElementTag {
+    fn from(tag: &str) -> Self {
+        match tag {
+            "html" => ElementTag::Html,
+            "head" => ElementTag::Head,
+            "body" => ElementTag::Body,
+            "title" => ElementTag::Title,
+            "meta" => ElementTag::Meta,
+            "link" => ElementTag::Link,
+            "style" => ElementTag::Style,
+            "script" => ElementTag::Script,
+            "div" => ElementTag::Div,
+            "span" => ElementTag::Span,
+            "p" => ElementTag::P,
+            "a" => ElementTag::A,
+            "ul" => ElementTag::Ul,
+            "ol" => ElementTag::Ol,
+            "li" => ElementTag::Li,
+            "nav" => ElementTag::Nav,
+            "header" => ElementTag::Header,
+            "footer" => ElementTag::Footer,
+            "section" => ElementTag::Section,
+            "article" => ElementTag::Article,
+            "h1" => ElementTag::H1,
+            "h2" => ElementTag::H2,
+            "h3" => ElementTag::H3,
+            "h4" => ElementTag::H4,
+            "h5" => ElementTag::H5,
+            "h6" => ElementTag::H6,
+            "strong" => ElementTag::Strong,
+            "em" => ElementTag::Em,
+            "b" => ElementTag::B,
+            "i" => ElementTag::I,
+            "code" => ElementTag::Code,
+            "pre" => ElementTag::Pre,
+            "blockquote" => ElementTag::Blockquote,
+            "table" => ElementTag::Table,
+            "thead" => ElementTag::Thead,
+            "tbody" => ElementTag::Tbody,
+            "tr" => ElementTag::Tr,
+            "td" => ElementTag::Td,
+            "th" => ElementTag::Th,
+            "form" => ElementTag::Form,
+            "label" => ElementTag::Label,
+            "button" => ElementTag::Button,
+            "img" => ElementTag::Img,
+            "br" => ElementTag::Br,
+            "hr" => ElementTag::Hr,
+            "input" => ElementTag::Input,
+            other => ElementTag::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for ElementTag {
+    fn from(tag: String) -> Self {
+        ElementTag::from(tag.as_str())
+    }
+}
+
+/// Serializable representation of a container's children.
+///
+/// Replaces `Box<dyn HtmlElement>` so an element tree — and therefore a
+/// whole document — can round-trip through `serde`: cached as JSON,
+/// shipped as a template fragment, or produced by a non-Rust service and
+/// rendered here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Content {
+    Element(ContainerElement),
+    Text(String),
+    Raw(String),
+    Image {
+        src: String,
+        alt: Option<String>,
+        attrs: HashMap<String, String>,
+    },
+}
+
+impl HtmlElement for Content {
+    fn tag(&self) -> &str {
+        match self {
+            Content::Element(el) => el.tag(),
+            Content::Text(_) => "span",
+            Content::Raw(_) => "",
+            Content::Image { .. } => "img",
+        }
+    }
+
+    fn attributes(&self) -> Option<Vec<(String, String)>> {
+        match self {
+            Content::Element(el) => el.attributes(),
+            Content::Text(_) | Content::Raw(_) => None,
+            Content::Image { src, alt, attrs } => {
+                let mut result = vec![("src".to_string(), encode_attribute(src))];
+                if let Some(alt) = alt {
+                    result.push(("alt".to_string(), encode_attribute(alt)));
+                }
+                for (key, value) in attrs {
+                    result.push((key.clone(), encode_attribute(value)));
+                }
+                Some(result)
+            }
+        }
+    }
+
+    fn text(&self) -> Option<String> {
+        match self {
+            Content::Element(el) => el.text(),
+            Content::Text(content) => Some(escape_text_content(content)),
+            Content::Raw(content) => Some(content.clone()),
+            Content::Image { .. } => None,
+        }
+    }
+
+    fn children(&self) -> Vec<&dyn HtmlElement> {
+        match self {
+            Content::Element(el) => el.children(),
+            Content::Text(_) | Content::Raw(_) | Content::Image { .. } => Vec::new(),
+        }
+    }
+
+    fn is_void_element(&self) -> bool {
+        match self {
+            Content::Element(el) => el.is_void_element(),
+            Content::Text(_) => false,
+            Content::Raw(_) => true,
+            Content::Image { .. } => true,
+        }
+    }
+}
+
+impl From<ContainerElement> for Content {
+    fn from(element: ContainerElement) -> Self {
+        Content::Element(element)
+    }
+}
+
+impl From<TextElement> for Content {
+    fn from(element: TextElement) -> Self {
+        Content::Text(element.content)
+    }
+}
+
+impl From<RawHtml> for Content {
+    fn from(element: RawHtml) -> Self {
+        Content::Raw(element.content)
+    }
+}
+
+impl From<ImageElement> for Content {
+    fn from(element: ImageElement) -> Self {
+        Content::Image {
+            src: element.src,
+            alt: element.alt,
+            attrs: element.attributes,
+        }
+    }
+}
+
 /// 容器元素（可嵌套）
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContainerElement {
-    tag: String,
+    tag: ElementTag,
     attributes: HashMap<String, String>,
-    children: Vec<Box<dyn HtmlElement>>,
+    children: Vec<Content>,
     classes: Vec<String>,
     id: Option<String>,
 }
 
 impl ContainerElement {
     /// 创建新的容器元素
-    pub fn new(tag: impl Into<String>) -> Self {
+    pub fn new(tag: impl Into<ElementTag>) -> Self {
         Self {
             tag: tag.into(),
             attributes: HashMap::new(),
@@ -199,29 +514,81 @@ impl ContainerElement {
     }
 
     /// 添加子元素
-    pub fn with_child(mut self, child: impl HtmlElement + 'static) -> Self {
-        self.children.push(Box::new(child));
+    pub fn with_child(mut self, child: impl Into<Content>) -> Self {
+        self.children.push(child.into());
         self
     }
-    
+
     /// Add multiple children at once
-    pub fn with_children(mut self, children: Vec<Box<dyn HtmlElement>>) -> Self {
+    pub fn with_children(mut self, children: Vec<Content>) -> Self {
         self.children.extend(children);
         self
     }
-    
+
+    /// Append a child in place, without the consuming-builder call
+    /// chain. Used by tree builders (e.g. the Markdown converter) that
+    /// need to mutate an in-progress container held on a stack.
+    pub(crate) fn push_content(&mut self, child: Content) {
+        self.children.push(child);
+    }
+
+    /// Consumes the container and returns its children, discarding the
+    /// wrapping tag. Used to re-wrap an already-built container's
+    /// content under a different tag (e.g. promoting a code block's
+    /// contents into a nested `<code>`).
+    pub(crate) fn into_children(self) -> Vec<Content> {
+        self.children
+    }
+
+    /// This container's tag, for callers that need to branch on it
+    /// (e.g. the table-of-contents walk looking for headings).
+    pub(crate) fn tag_ref(&self) -> &ElementTag {
+        &self.tag
+    }
+
+    /// The id already set on this element, if any.
+    pub(crate) fn id_ref(&self) -> Option<&String> {
+        self.id.as_ref()
+    }
+
+    /// Sets this element's id in place, overwriting any existing one.
+    pub(crate) fn set_id(&mut self, id: String) {
+        self.id = Some(id);
+    }
+
+    /// Mutable access to this container's children, for in-place tree
+    /// transforms (e.g. assigning heading ids for a table of contents).
+    pub(crate) fn children_mut(&mut self) -> &mut Vec<Content> {
+        &mut self.children
+    }
+
+    /// Concatenates all literal text nested under this container,
+    /// ignoring markup. Used to derive stable slugs for auto-generated
+    /// heading ids.
+    pub(crate) fn text_content(&self) -> String {
+        let mut out = String::new();
+        for child in &self.children {
+            match child {
+                Content::Text(text) => out.push_str(text),
+                Content::Element(element) => out.push_str(&element.text_content()),
+                Content::Raw(_) | Content::Image { .. } => {}
+            }
+        }
+        out
+    }
+
     /// Add a class to the element
     pub fn with_class(mut self, class: impl Into<String>) -> Self {
         self.classes.push(class.into());
         self
     }
-    
+
     /// Set element ID
     pub fn with_id(mut self, id: impl Into<String>) -> Self {
         self.id = Some(id.into());
         self
     }
-    
+
     /// Add text content to the container
     pub fn with_text(self, text: impl Into<String>) -> Self {
         self.with_child(TextElement::new(text))
@@ -230,23 +597,23 @@ impl ContainerElement {
 
 impl HtmlElement for ContainerElement {
     fn tag(&self) -> &str {
-        &self.tag
+        self.tag.name()
     }
 
     fn attributes(&self) -> Option<Vec<(String, String)>> {
         let mut result = Vec::new();
-        
+
         // Add ID if present
         if let Some(id) = &self.id {
             result.push(("id".to_string(), encode_attribute(id).to_string()));
         }
-        
+
         // Add classes if any
         if !self.classes.is_empty() {
             let class_str = self.classes.join(" ");
             result.push(("class".to_string(), encode_attribute(&class_str).to_string()));
         }
-        
+
         // Add all other attributes
         for (key, value) in &self.attributes {
             result.push((
@@ -254,7 +621,7 @@ impl HtmlElement for ContainerElement {
                 encode_attribute(value).to_string(),
             ));
         }
-        
+
         if result.is_empty() {
             None
         } else {
@@ -267,11 +634,11 @@ impl HtmlElement for ContainerElement {
     }
 
     fn children(&self) -> Vec<&dyn HtmlElement> {
-        self.children.iter().map(|c| c.as_ref()).collect()
+        self.children.iter().map(|c| c as &dyn HtmlElement).collect()
     }
 
     fn is_void_element(&self) -> bool {
-        false
+        self.tag.is_void()
     }
 }
 
@@ -282,11 +649,12 @@ impl fmt::Display for ContainerElement {
 }
 
 /// HTML文档构建器
+#[derive(Debug, Serialize, Deserialize)]
 pub struct HtmlDocumentBuilder {
     doctype: String,
     title: Option<String>,
-    head_elements: Vec<Box<dyn HtmlElement>>,
-    body_elements: Vec<Box<dyn HtmlElement>>,
+    head_elements: Vec<Content>,
+    body_elements: Vec<Content>,
     lang: Option<String>,
     meta_tags: Vec<(String, String)>,
     stylesheets: Vec<String>,
@@ -323,14 +691,14 @@ impl HtmlDocumentBuilder {
     }
 
     /// 添加head区域元素
-    pub fn add_head_element(mut self, element: impl HtmlElement + 'static) -> Self {
-        self.head_elements.push(Box::new(element));
+    pub fn add_head_element(mut self, element: impl Into<Content>) -> Self {
+        self.head_elements.push(element.into());
         self
     }
 
     /// 添加body区域元素
-    pub fn add_body_element(mut self, element: impl HtmlElement + 'static) -> Self {
-        self.body_elements.push(Box::new(element));
+    pub fn add_body_element(mut self, element: impl Into<Content>) -> Self {
+        self.body_elements.push(element.into());
         self
     }
     
@@ -364,6 +732,15 @@ impl HtmlDocumentBuilder {
         self
     }
 
+    /// Walks the body elements built so far, assigns an `id` to every
+    /// heading that doesn't already have one, and inserts a `<nav>` table
+    /// of contents linking to them as the first body element.
+    pub fn generate_toc(mut self) -> Self {
+        let nav = crate::toc::generate_toc(&mut self.body_elements);
+        self.body_elements.insert(0, nav);
+        self
+    }
+
     /// 构建完整HTML文档
     pub fn build(self) -> String {
         let mut output = String::new();
@@ -413,7 +790,7 @@ impl HtmlDocumentBuilder {
         
         // Add other head elements
         for element in &self.head_elements {
-            output.push_str(&render_element(&**element)); // 双重解引用转换为 trait 对象
+            output.push_str(&render_element(element));
             output.push('\n');
         }
         output.push_str("</head>\n");
@@ -431,7 +808,7 @@ impl HtmlDocumentBuilder {
         
         // Add body elements
         for element in &self.body_elements {
-            output.push_str(&render_element(&**element));
+            output.push_str(&render_element(element));
             output.push('\n');
         }
         
@@ -447,6 +824,374 @@ impl HtmlDocumentBuilder {
 
         output
     }
+
+    /// Builds the document like `build()`, but renders head and body
+    /// elements with [`HtmlElement::render_pretty`] under `opts` instead
+    /// of `render()`'s single unbroken line per element.
+    pub fn build_pretty(self, opts: PrettyOptions) -> String {
+        let mut output = String::new();
+
+        output.push_str(&self.doctype);
+
+        if let Some(lang) = &self.lang {
+            output.push_str(&format!("\n<html lang=\"{}\">", encode_attribute(lang)));
+        } else {
+            output.push_str("\n<html>");
+        }
+        output.push('\n');
+
+        output.push_str("<head>\n");
+
+        for (name, content) in &self.meta_tags {
+            if name == "charset" {
+                output.push_str(&format!("<meta charset=\"{}\">\n", encode_attribute(content)));
+            } else {
+                output.push_str(&format!(
+                    "<meta name=\"{}\" content=\"{}\">\n",
+                    encode_attribute(name),
+                    encode_attribute(content)
+                ));
+            }
+        }
+
+        if let Some(title) = &self.title {
+            output.push_str(&format!("<title>{}</title>\n", encode_text(title)));
+        }
+
+        for href in &self.stylesheets {
+            output.push_str(&format!(
+                "<link rel=\"stylesheet\" href=\"{}\">\n",
+                encode_attribute(href)
+            ));
+        }
+
+        for element in &self.head_elements {
+            render_pretty_block(element, 1, &opts, &mut output);
+        }
+        output.push_str("</head>\n");
+
+        output.push_str("<body");
+        for (name, value) in &self.body_attributes {
+            output.push_str(&format!(
+                " {}=\"{}\"",
+                encode_attribute(name),
+                encode_attribute(value)
+            ));
+        }
+        output.push_str(">\n");
+
+        for element in &self.body_elements {
+            render_pretty_block(element, 1, &opts, &mut output);
+        }
+
+        for src in &self.scripts {
+            output.push_str(&format!(
+                "<script src=\"{}\"></script>\n",
+                encode_attribute(src)
+            ));
+        }
+
+        output.push_str("</body>\n</html>");
+
+        output
+    }
+
+    /// Builds the document like `build()`, but validates every head and
+    /// body element first and reports the first problem found instead of
+    /// emitting broken markup.
+    pub fn serialize(self) -> std::result::Result<String, HtmlError> {
+        let mut output = String::new();
+
+        output.push_str(&self.doctype);
+
+        if let Some(lang) = &self.lang {
+            output.push_str(&format!("\n<html lang=\"{}\">", encode_attribute(lang)));
+        } else {
+            output.push_str("\n<html>");
+        }
+        output.push('\n');
+
+        output.push_str("<head>\n");
+
+        for (name, content) in &self.meta_tags {
+            if name == "charset" {
+                output.push_str(&format!("<meta charset=\"{}\">\n", encode_attribute(content)));
+            } else {
+                output.push_str(&format!(
+                    "<meta name=\"{}\" content=\"{}\">\n",
+                    encode_attribute(name),
+                    encode_attribute(content)
+                ));
+            }
+        }
+
+        if let Some(title) = &self.title {
+            output.push_str(&format!("<title>{}</title>\n", encode_text(title)));
+        }
+
+        for href in &self.stylesheets {
+            output.push_str(&format!(
+                "<link rel=\"stylesheet\" href=\"{}\">\n",
+                encode_attribute(href)
+            ));
+        }
+
+        for element in &self.head_elements {
+            output.push_str(&validate_and_render(element)?);
+            output.push('\n');
+        }
+        output.push_str("</head>\n");
+
+        output.push_str("<body");
+        for (name, value) in &self.body_attributes {
+            output.push_str(&format!(
+                " {}=\"{}\"",
+                encode_attribute(name),
+                encode_attribute(value)
+            ));
+        }
+        output.push_str(">\n");
+
+        for element in &self.body_elements {
+            output.push_str(&validate_and_render(element)?);
+            output.push('\n');
+        }
+
+        for src in &self.scripts {
+            output.push_str(&format!(
+                "<script src=\"{}\"></script>\n",
+                encode_attribute(src)
+            ));
+        }
+
+        output.push_str("</body>\n</html>");
+
+        Ok(output)
+    }
+}
+
+/// Errors reported by [`HtmlElement::render_checked`] and
+/// [`HtmlDocumentBuilder::serialize`] when a tree can't be rendered into
+/// valid HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HtmlError {
+    /// A void element (e.g. `<img/>`) was given children, which have
+    /// nowhere to go since void elements have no closing tag.
+    VoidElementWithChildren(String),
+    /// The tag name was empty or whitespace-only.
+    EmptyTagName,
+    /// The tag name contains characters that can't appear in a tag, e.g.
+    /// whitespace or `<`/`>`.
+    InvalidTagName(String),
+    /// The attribute name contains characters that can't appear in an
+    /// attribute name, e.g. whitespace or a quote.
+    InvalidAttributeName(String),
+}
+
+impl fmt::Display for HtmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HtmlError::VoidElementWithChildren(tag) => {
+                write!(f, "void element <{}> was given children", tag)
+            }
+            HtmlError::EmptyTagName => write!(f, "tag name is empty or whitespace-only"),
+            HtmlError::InvalidTagName(tag) => write!(f, "invalid tag name: {:?}", tag),
+            HtmlError::InvalidAttributeName(name) => {
+                write!(f, "invalid attribute name: {:?}", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HtmlError {}
+
+/// Returns true for tag names made up only of ASCII letters, digits and
+/// hyphens (covers both the standard vocabulary and custom elements like
+/// `my-widget`).
+fn is_valid_tag_name(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Returns true for non-empty attribute names that don't contain
+/// whitespace, quote characters, `=`, `<` or `>`, any of which would break
+/// out of the `name="value"` slot.
+fn is_valid_attribute_name(name: &str) -> bool {
+    !name.is_empty() && !name.chars().any(|c| c.is_whitespace() || c == '"' || c == '\'' || c == '=' || c == '<' || c == '>')
+}
+
+/// Like `render_element`, but checks each element for the mistakes
+/// `render_element` would otherwise silently paper over.
+fn validate_and_render(element: &dyn HtmlElement) -> std::result::Result<String, HtmlError> {
+    // Special case for raw HTML, mirroring render_element.
+    if element.tag().is_empty() && element.is_void_element() {
+        return Ok(element.text().unwrap_or_default());
+    }
+
+    let tag = element.tag();
+    if tag.trim().is_empty() {
+        return Err(HtmlError::EmptyTagName);
+    }
+    if !is_valid_tag_name(tag) {
+        return Err(HtmlError::InvalidTagName(tag.to_string()));
+    }
+
+    if element.is_void_element() && !element.children().is_empty() {
+        return Err(HtmlError::VoidElementWithChildren(tag.to_string()));
+    }
+
+    let mut html = String::new();
+    html.push_str(&format!("<{}", tag));
+
+    if let Some(attrs) = element.attributes() {
+        for (key, value) in attrs {
+            if !is_valid_attribute_name(&key) {
+                return Err(HtmlError::InvalidAttributeName(key));
+            }
+            html.push_str(&format!(" {}=\"{}\"", key, value));
+        }
+    }
+
+    if element.is_void_element() {
+        html.push_str(" />");
+        return Ok(html);
+    }
+
+    html.push('>');
+
+    if let Some(text) = element.text() {
+        html.push_str(&text);
+    }
+
+    for child in element.children() {
+        html.push_str(&validate_and_render(child)?);
+    }
+
+    html.push_str(&format!("</{}>", tag));
+
+    Ok(html)
+}
+
+/// Options controlling [`HtmlElement::render_pretty`]'s indentation.
+#[derive(Debug, Clone)]
+pub struct PrettyOptions {
+    indent: String,
+}
+
+impl PrettyOptions {
+    /// Indent each nesting level with `width` spaces.
+    pub fn with_indent_width(mut self, width: usize) -> Self {
+        self.indent = " ".repeat(width);
+        self
+    }
+
+    /// Indent each nesting level with a single tab.
+    pub fn with_tabs(mut self) -> Self {
+        self.indent = "\t".to_string();
+        self
+    }
+
+    /// Indent each nesting level with an arbitrary unit, e.g. `"  "` or
+    /// `"\t\t"`.
+    pub fn with_indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = indent.into();
+        self
+    }
+}
+
+impl Default for PrettyOptions {
+    /// Two spaces per nesting level.
+    fn default() -> Self {
+        Self { indent: "  ".to_string() }
+    }
+}
+
+/// Tags whose content is phrasing/inline and should stay on the same
+/// line as their parent rather than being broken out, e.g. the `<strong>`
+/// inside `<p>Some <strong>text</strong></p>`. Text runs render as
+/// `<span>` (see `TextElement::tag`) so they're covered by this too, as
+/// is raw HTML (empty tag name).
+fn is_inline_tag(tag: &str) -> bool {
+    tag.is_empty()
+        || matches!(
+            tag,
+            "span" | "a" | "em" | "strong" | "code" | "b" | "i" | "small" | "sub" | "sup" | "mark" | "abbr" | "q" | "cite"
+        )
+}
+
+/// Renders `element` and its descendants one nested element per line at
+/// `depth`, falling back to the compact single-line form (via
+/// `render_element`) for the children of `<pre>` and of any element whose
+/// children are all inline. Mixed inline and block children are each put
+/// on their own line.
+fn render_pretty_block(element: &dyn HtmlElement, depth: usize, opts: &PrettyOptions, out: &mut String) {
+    let indent = opts.indent.repeat(depth);
+
+    if element.tag().is_empty() && element.is_void_element() {
+        out.push_str(&indent);
+        out.push_str(&element.text().unwrap_or_default());
+        out.push('\n');
+        return;
+    }
+
+    let tag = element.tag();
+    out.push_str(&indent);
+    out.push_str(&format!("<{}", tag));
+    if let Some(attrs) = element.attributes() {
+        for (key, value) in attrs {
+            out.push_str(&format!(" {}=\"{}\"", key, value));
+        }
+    }
+
+    if element.is_void_element() {
+        out.push_str(" />\n");
+        return;
+    }
+    out.push('>');
+
+    let text = element.text();
+    let children = element.children();
+
+    if children.is_empty() {
+        if let Some(text) = &text {
+            out.push_str(text);
+        }
+        out.push_str(&format!("</{}>\n", tag));
+        return;
+    }
+
+    // <pre> preserves whitespace verbatim, so breaking its content across
+    // indented lines (like any other block element) would change what it
+    // renders. Keep it exactly as `render_element` would, on one line.
+    if tag == "pre" {
+        if let Some(text) = &text {
+            out.push_str(text);
+        }
+        for child in &children {
+            out.push_str(&render_element(*child));
+        }
+        out.push_str(&format!("</{}>\n", tag));
+        return;
+    }
+
+    if text.is_none() && children.iter().all(|child| is_inline_tag(child.tag())) {
+        for child in &children {
+            out.push_str(&render_element(*child));
+        }
+        out.push_str(&format!("</{}>\n", tag));
+        return;
+    }
+
+    out.push('\n');
+    if let Some(text) = &text {
+        out.push_str(&opts.indent.repeat(depth + 1));
+        out.push_str(text);
+        out.push('\n');
+    }
+    for child in &children {
+        render_pretty_block(*child, depth + 1, opts, out);
+    }
+    out.push_str(&indent);
+    out.push_str(&format!("</{}>\n", tag));
 }
 
 /// 递归渲染HTML元素
@@ -561,4 +1306,182 @@ fn test_xss_protection() {
         assert!(rendered.contains("id=\"main-content\""));
         assert!(rendered.contains("class=\"primary large\""));
     }
+
+    #[test]
+    fn test_element_tag_name_round_trips_through_from_str() {
+        for name in ["div", "span", "h1", "img", "br", "custom-widget"] {
+            assert_eq!(ElementTag::from(name).name(), name);
+        }
+    }
+
+    #[test]
+    fn test_element_tag_is_void() {
+        assert!(ElementTag::Img.is_void());
+        assert!(ElementTag::Br.is_void());
+        assert!(ElementTag::Hr.is_void());
+        assert!(ElementTag::Input.is_void());
+        assert!(ElementTag::Meta.is_void());
+        assert!(ElementTag::Link.is_void());
+        assert!(!ElementTag::Div.is_void());
+        assert!(!ElementTag::Other("custom-widget".to_string()).is_void());
+    }
+
+    #[test]
+    fn test_element_tag_other_is_an_escape_hatch() {
+        let tag = ElementTag::from("marquee");
+        assert_eq!(tag, ElementTag::Other("marquee".to_string()));
+        assert_eq!(tag.name(), "marquee");
+    }
+
+    #[test]
+    fn test_content_tree_round_trips_through_serde_json() {
+        let original = Content::Element(
+            ContainerElement::new(ElementTag::Div)
+                .with_class("note")
+                .with_id("intro")
+                .with_child(TextElement::new("hello"))
+                .with_child(ContainerElement::new(ElementTag::Other("marquee".to_string())).with_text("scrolling")),
+        );
+
+        let json = serde_json::to_string(&original).expect("Content should serialize");
+        let restored: Content = serde_json::from_str(&json).expect("Content should deserialize");
+
+        assert_eq!(restored.render(), original.render());
+    }
+
+    #[test]
+    fn test_html_document_builder_round_trips_through_serde_json() {
+        let original = HtmlDocumentBuilder::new()
+            .title("Round Trip")
+            .add_body_element(TextElement::new("hi"));
+
+        let json = serde_json::to_string(&original).expect("HtmlDocumentBuilder should serialize");
+        let restored: HtmlDocumentBuilder =
+            serde_json::from_str(&json).expect("HtmlDocumentBuilder should deserialize");
+
+        assert_eq!(restored.build(), original.build());
+    }
+
+    #[test]
+    fn test_serialize_rejects_void_element_with_children() {
+        let img = ContainerElement::new(ElementTag::Img).with_child(TextElement::new("nope"));
+        let err = img.render_checked().unwrap_err();
+        assert_eq!(err, HtmlError::VoidElementWithChildren("img".to_string()));
+    }
+
+    #[test]
+    fn test_serialize_rejects_empty_tag_name() {
+        let element = ContainerElement::new("   ");
+        let err = element.render_checked().unwrap_err();
+        assert_eq!(err, HtmlError::EmptyTagName);
+    }
+
+    #[test]
+    fn test_serialize_rejects_invalid_tag_name() {
+        let element = ContainerElement::new("div class");
+        let err = element.render_checked().unwrap_err();
+        assert_eq!(err, HtmlError::InvalidTagName("div class".to_string()));
+    }
+
+    #[test]
+    fn test_serialize_rejects_invalid_attribute_name() {
+        let element = ContainerElement::new("div").with_attribute("data-\"bad", "value");
+        let err = element.render_checked().unwrap_err();
+        assert_eq!(err, HtmlError::InvalidAttributeName("data-\"bad".to_string()));
+    }
+
+    #[test]
+    fn test_serialize_accepts_a_well_formed_tree() {
+        let element = ContainerElement::new("div")
+            .with_attribute("data-id", "1")
+            .with_child(TextElement::new("ok"));
+        assert!(element.render_checked().is_ok());
+    }
+
+    #[test]
+    fn test_html_document_builder_serialize_propagates_element_errors() {
+        let bad = ContainerElement::new(ElementTag::Img).with_child(TextElement::new("nope"));
+        let err = HtmlDocumentBuilder::new().add_body_element(bad).serialize().unwrap_err();
+        assert_eq!(err, HtmlError::VoidElementWithChildren("img".to_string()));
+    }
+
+    #[test]
+    fn test_render_pretty_collapses_inline_children_onto_one_line() {
+        let p = ContainerElement::new(ElementTag::P)
+            .with_child(TextElement::new("Some "))
+            .with_child(ContainerElement::new(ElementTag::Strong).with_text("bold"))
+            .with_child(TextElement::new(" text"));
+
+        let rendered = p.render_pretty(PrettyOptions::default());
+        assert_eq!(
+            rendered,
+            "<p><span>Some </span><strong><span>bold</span></strong><span> text</span></p>"
+        );
+    }
+
+    #[test]
+    fn test_render_pretty_collapses_code_and_emphasis_inline() {
+        let p = ContainerElement::new(ElementTag::P)
+            .with_child(TextElement::new("Run "))
+            .with_child(ContainerElement::new(ElementTag::Code).with_text("ls -la"))
+            .with_child(TextElement::new(" as "))
+            .with_child(ContainerElement::new(ElementTag::B).with_text("root"));
+
+        let rendered = p.render_pretty(PrettyOptions::default());
+        assert_eq!(
+            rendered,
+            "<p><span>Run </span><code><span>ls -la</span></code><span> as </span><b><span>root</span></b></p>"
+        );
+    }
+
+    #[test]
+    fn test_render_pretty_indents_nested_block_elements() {
+        let div = ContainerElement::new(ElementTag::Div)
+            .with_child(ContainerElement::new(ElementTag::P).with_text("one"))
+            .with_child(ContainerElement::new(ElementTag::P).with_text("two"));
+
+        let rendered = div.render_pretty(PrettyOptions::default());
+        assert_eq!(
+            rendered,
+            "<div>\n  <p><span>one</span></p>\n  <p><span>two</span></p>\n</div>"
+        );
+    }
+
+    #[test]
+    fn test_render_pretty_preserves_pre_whitespace() {
+        let code = ContainerElement::new(ElementTag::Code).with_text("line1\nline2");
+        let pre = ContainerElement::new(ElementTag::Pre).with_child(code);
+
+        let rendered = pre.render_pretty(PrettyOptions::default());
+        assert_eq!(rendered, "<pre><code><span>line1\nline2</span></code></pre>");
+    }
+
+    #[test]
+    fn test_pretty_options_custom_indent_width() {
+        let div = ContainerElement::new(ElementTag::Div)
+            .with_child(ContainerElement::new(ElementTag::Div).with_child(ContainerElement::new(ElementTag::P).with_text("x")));
+
+        let rendered = div.render_pretty(PrettyOptions::default().with_indent_width(4));
+        assert!(rendered.contains("\n    <div>\n        <p>"));
+    }
+
+    #[test]
+    fn test_pretty_options_tabs() {
+        let div = ContainerElement::new(ElementTag::Div).with_child(ContainerElement::new(ElementTag::P).with_text("x"));
+
+        let rendered = div.render_pretty(PrettyOptions::default().with_tabs());
+        assert!(rendered.contains("\n\t<p>"));
+    }
+
+    #[test]
+    fn test_build_pretty_indents_body_elements() {
+        let doc = HtmlDocumentBuilder::new()
+            .title("T")
+            .add_body_element(
+                ContainerElement::new(ElementTag::Div).with_child(ContainerElement::new(ElementTag::P).with_text("hi")),
+            )
+            .build_pretty(PrettyOptions::default());
+
+        assert!(doc.contains("<body>\n  <div>\n    <p><span>hi</span></p>\n  </div>\n"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,6 @@
+pub mod html_file;
+pub mod markdown;
+pub mod sanitize;
+pub mod toc;
+
+pub use html_file::*;
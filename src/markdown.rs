@@ -0,0 +1,205 @@
+//! Converts Markdown source into the crate's `HtmlElement` tree, so body
+//! content can be authored in Markdown and dropped straight into
+//! `HtmlDocumentBuilder::add_body_element` without hand-assembling every
+//! `<p>` and `<li>`.
+
+use crate::html_file::{Content, ContainerElement, ElementTag};
+use pulldown_cmark::{CowStr, HeadingLevel, Parser, Tag, TagEnd};
+use std::collections::HashMap;
+
+/// A container being built, or a pending image whose alt text is still
+/// streaming in as nested `Event::Text`.
+enum Frame {
+    Container(ContainerElement),
+    Image { src: String, title: String, alt: String },
+}
+
+/// Parses `markdown` and returns its top-level block elements in order.
+pub fn parse_markdown(markdown: &str) -> Vec<Content> {
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut roots: Vec<Content> = Vec::new();
+
+    for event in Parser::new(markdown) {
+        match event {
+            pulldown_cmark::Event::Start(tag) => stack.push(start_frame(tag)),
+            pulldown_cmark::Event::End(tag_end) => end_frame(tag_end, &mut stack, &mut roots),
+            pulldown_cmark::Event::Text(text) => push_text(text, &mut stack, &mut roots),
+            pulldown_cmark::Event::Code(text) => {
+                let code = ContainerElement::new(ElementTag::Code).with_text(text.to_string());
+                push(Content::Element(code), &mut stack, &mut roots);
+            }
+            pulldown_cmark::Event::Html(html) | pulldown_cmark::Event::InlineHtml(html) => {
+                push(Content::Raw(html.to_string()), &mut stack, &mut roots);
+            }
+            pulldown_cmark::Event::SoftBreak => push(Content::Raw(" ".to_string()), &mut stack, &mut roots),
+            pulldown_cmark::Event::HardBreak => {
+                push(Content::Element(ContainerElement::new(ElementTag::Br)), &mut stack, &mut roots)
+            }
+            pulldown_cmark::Event::Rule => {
+                push(Content::Element(ContainerElement::new(ElementTag::Hr)), &mut stack, &mut roots)
+            }
+            _ => {}
+        }
+    }
+
+    roots
+}
+
+fn start_frame(tag: Tag) -> Frame {
+    match tag {
+        Tag::Heading { level, .. } => Frame::Container(ContainerElement::new(heading_tag(level))),
+        Tag::Paragraph => Frame::Container(ContainerElement::new(ElementTag::P)),
+        Tag::BlockQuote(_) => Frame::Container(ContainerElement::new(ElementTag::Blockquote)),
+        Tag::List(None) => Frame::Container(ContainerElement::new(ElementTag::Ul)),
+        Tag::List(Some(start)) => {
+            let ol = ContainerElement::new(ElementTag::Ol);
+            let ol = if start != 1 {
+                ol.with_attribute("start", start.to_string())
+            } else {
+                ol
+            };
+            Frame::Container(ol)
+        }
+        Tag::Item => Frame::Container(ContainerElement::new(ElementTag::Li)),
+        Tag::Emphasis => Frame::Container(ContainerElement::new(ElementTag::Em)),
+        Tag::Strong => Frame::Container(ContainerElement::new(ElementTag::Strong)),
+        Tag::CodeBlock(_) => Frame::Container(ContainerElement::new(ElementTag::Pre)),
+        Tag::Link { dest_url, .. } => {
+            Frame::Container(ContainerElement::new(ElementTag::A).with_attribute("href", dest_url.to_string()))
+        }
+        Tag::Image { dest_url, title, .. } => Frame::Image {
+            src: dest_url.to_string(),
+            title: title.to_string(),
+            alt: String::new(),
+        },
+        // Anything else (tables, footnotes, metadata blocks, ...) is
+        // flattened into a plain `div` rather than dropped silently.
+        _ => Frame::Container(ContainerElement::new(ElementTag::Div)),
+    }
+}
+
+fn end_frame(tag_end: TagEnd, stack: &mut Vec<Frame>, roots: &mut Vec<Content>) {
+    let frame = match stack.pop() {
+        Some(frame) => frame,
+        None => return,
+    };
+
+    match frame {
+        Frame::Image { src, title, alt } => {
+            let mut attrs = HashMap::new();
+            if !title.is_empty() {
+                attrs.insert("title".to_string(), title);
+            }
+            let alt = if alt.is_empty() { None } else { Some(alt) };
+            push(Content::Image { src, alt, attrs }, stack, roots);
+        }
+        Frame::Container(container) if matches!(tag_end, TagEnd::CodeBlock) => {
+            let code = ContainerElement::new(ElementTag::Code).with_children(container.into_children());
+            let pre = ContainerElement::new(ElementTag::Pre).with_child(code);
+            push(Content::Element(pre), stack, roots);
+        }
+        Frame::Container(container) => push(Content::Element(container), stack, roots),
+    }
+}
+
+fn push_text(text: CowStr, stack: &mut [Frame], roots: &mut Vec<Content>) {
+    push(Content::Text(text.to_string()), stack, roots);
+}
+
+/// Pushes `content` onto the frame currently open at the top of `stack`
+/// (or `roots` if the stack is empty). When an image frame is on top,
+/// nothing can nest under an `<img>`, so any text inside `content` (e.g.
+/// a `**bold**` span within `![**bold**](x.png)`) is flattened into the
+/// image's alt buffer instead of being dropped.
+fn push(content: Content, stack: &mut [Frame], roots: &mut Vec<Content>) {
+    match stack.last_mut() {
+        Some(Frame::Container(parent)) => parent.push_content(content),
+        Some(Frame::Image { alt, .. }) => alt.push_str(&content_text(&content)),
+        None => roots.push(content),
+    }
+}
+
+/// Extracts the literal text out of `content`, recursing into nested
+/// elements (e.g. the text inside a `<strong>`) but yielding nothing for
+/// raw HTML or a nested image, which have no plain-text equivalent.
+fn content_text(content: &Content) -> String {
+    match content {
+        Content::Text(text) => text.clone(),
+        Content::Element(element) => element.text_content(),
+        Content::Raw(_) | Content::Image { .. } => String::new(),
+    }
+}
+
+fn heading_tag(level: HeadingLevel) -> ElementTag {
+    match level {
+        HeadingLevel::H1 => ElementTag::H1,
+        HeadingLevel::H2 => ElementTag::H2,
+        HeadingLevel::H3 => ElementTag::H3,
+        HeadingLevel::H4 => ElementTag::H4,
+        HeadingLevel::H5 => ElementTag::H5,
+        HeadingLevel::H6 => ElementTag::H6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html_file::HtmlElement;
+
+    #[test]
+    fn test_heading_with_nested_markup() {
+        let roots = parse_markdown("# Hello **World**");
+        assert_eq!(roots.len(), 1);
+        let rendered = roots[0].render();
+        assert!(rendered.starts_with("<h1>"));
+        // Text renders wrapped in `<span>`, same as every other
+        // `Content::Text` in this crate.
+        assert!(rendered.contains("<strong><span>World</span></strong>"));
+    }
+
+    #[test]
+    fn test_list_with_nested_markup() {
+        let roots = parse_markdown("- one\n- **two**");
+        assert_eq!(roots.len(), 1);
+        let rendered = roots[0].render();
+        assert!(rendered.starts_with("<ul>"));
+        assert!(rendered.contains("<strong><span>two</span></strong>"));
+    }
+
+    #[test]
+    fn test_ordered_list_default_start_is_omitted() {
+        let roots = parse_markdown("1. one\n2. two");
+        let rendered = roots[0].render();
+        assert!(rendered.starts_with("<ol>"));
+        assert!(!rendered.contains("start="));
+    }
+
+    #[test]
+    fn test_ordered_list_nondefault_start_attribute() {
+        let roots = parse_markdown("3. one\n4. two");
+        let rendered = roots[0].render();
+        assert!(rendered.starts_with("<ol start=\"3\">"));
+    }
+
+    #[test]
+    fn test_image_alt_text_with_nested_markup_is_preserved() {
+        let mut roots = parse_markdown("![**bold** alt](x.png)");
+        assert_eq!(roots.len(), 1);
+        // CommonMark wraps a standalone image in a paragraph, so the image
+        // itself is the lone child of that `<p>`, not a root.
+        match roots.remove(0) {
+            Content::Element(p) => {
+                let mut children = p.into_children();
+                assert_eq!(children.len(), 1);
+                match children.remove(0) {
+                    Content::Image { src, alt, .. } => {
+                        assert_eq!(src, "x.png");
+                        assert_eq!(alt.as_deref(), Some("bold alt"));
+                    }
+                    other => panic!("expected an image, got {other:?}"),
+                }
+            }
+            other => panic!("expected a paragraph wrapping the image, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,518 @@
+//! Allowlist-based sanitizer for untrusted HTML (e.g. user-submitted rich
+//! text), turning it into the crate's own element tree instead of the
+//! raw, completely unescaped string `RawHtml` would otherwise emit.
+//!
+//! Unknown tags are dropped but their text is kept; disallowed attributes
+//! and unsafe URL schemes (`javascript:`, `data:`, ...) are stripped
+//! rather than failing the whole document.
+
+use crate::html_file::{Content, ContainerElement, ElementTag};
+use std::collections::{HashMap, HashSet};
+
+/// A callback that rewrites an attribute's value, optionally renaming the
+/// attribute itself (e.g. `src` -> `data-src`, so an image can't load
+/// until the caller explicitly opts in).
+pub type RewriteAttr = Box<dyn Fn(&str) -> (String, String) + Send + Sync>;
+
+/// What a [`sanitize`] call is allowed to keep from the source HTML.
+pub struct SanitizePolicy {
+    allowed_tags: HashSet<ElementTag>,
+    allowed_attrs: HashMap<ElementTag, HashSet<String>>,
+    global_attrs: HashSet<String>,
+    allowed_schemes: HashSet<String>,
+    rewrite_attrs: HashMap<String, RewriteAttr>,
+}
+
+impl SanitizePolicy {
+    /// A policy that allows nothing at all; every tag is stripped down to
+    /// its text. Start here when building a custom allowlist.
+    pub fn empty() -> Self {
+        Self {
+            allowed_tags: HashSet::new(),
+            allowed_attrs: HashMap::new(),
+            global_attrs: HashSet::new(),
+            allowed_schemes: HashSet::new(),
+            rewrite_attrs: HashMap::new(),
+        }
+    }
+
+    /// Allow a tag to pass through (still subject to its own attribute
+    /// allowlist).
+    pub fn allow_tag(mut self, tag: impl Into<ElementTag>) -> Self {
+        self.allowed_tags.insert(tag.into());
+        self
+    }
+
+    /// Allow `attr` on `tag` specifically, e.g. `href` on `a`.
+    pub fn allow_attr(mut self, tag: impl Into<ElementTag>, attr: impl Into<String>) -> Self {
+        self.allowed_attrs.entry(tag.into()).or_default().insert(attr.into());
+        self
+    }
+
+    /// Allow `attr` on every tag, e.g. `title`.
+    pub fn allow_global_attr(mut self, attr: impl Into<String>) -> Self {
+        self.global_attrs.insert(attr.into());
+        self
+    }
+
+    /// Allow `scheme` (e.g. `"https"`) in `href`/`src` values. URLs with
+    /// no scheme (relative links) are always allowed.
+    pub fn allow_scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.allowed_schemes.insert(scheme.into());
+        self
+    }
+
+    /// Rewrite every occurrence of `attr`, renaming it and/or transforming
+    /// its value. Runs after the tag/attribute/scheme allowlist, so the
+    /// attribute must already be allowed for `rewrite` to see it.
+    pub fn rewrite_attr(mut self, attr: impl Into<String>, rewrite: impl Fn(&str) -> (String, String) + Send + Sync + 'static) -> Self {
+        self.rewrite_attrs.insert(attr.into(), Box::new(rewrite));
+        self
+    }
+
+    fn attrs_allowed_for(&self, tag: &ElementTag) -> HashSet<String> {
+        let mut allowed = self.global_attrs.clone();
+        if let Some(tag_attrs) = self.allowed_attrs.get(tag) {
+            allowed.extend(tag_attrs.iter().cloned());
+        }
+        allowed
+    }
+}
+
+impl Default for SanitizePolicy {
+    /// A "basic formatting" policy safe for rendering untrusted rich
+    /// text: paragraphs, inline emphasis, lists, links and code, with
+    /// `href` restricted to `http`/`https`/`mailto`.
+    fn default() -> Self {
+        Self::empty()
+            .allow_tag(ElementTag::P)
+            .allow_tag(ElementTag::Br)
+            .allow_tag(ElementTag::Strong)
+            .allow_tag(ElementTag::Em)
+            .allow_tag(ElementTag::B)
+            .allow_tag(ElementTag::I)
+            .allow_tag(ElementTag::Ul)
+            .allow_tag(ElementTag::Ol)
+            .allow_tag(ElementTag::Li)
+            .allow_tag(ElementTag::Blockquote)
+            .allow_tag(ElementTag::Code)
+            .allow_tag(ElementTag::Pre)
+            .allow_tag(ElementTag::H1)
+            .allow_tag(ElementTag::H2)
+            .allow_tag(ElementTag::H3)
+            .allow_tag(ElementTag::H4)
+            .allow_tag(ElementTag::H5)
+            .allow_tag(ElementTag::H6)
+            .allow_tag(ElementTag::A)
+            .allow_attr(ElementTag::A, "href")
+            .allow_attr(ElementTag::A, "title")
+            .allow_scheme("http")
+            .allow_scheme("https")
+            .allow_scheme("mailto")
+    }
+}
+
+/// Parses `html` and rebuilds it as a sanitized element tree under
+/// `policy`: disallowed tags are dropped but their text children are
+/// preserved, disallowed attributes and URL schemes are stripped, and any
+/// configured [`SanitizePolicy::rewrite_attr`] callbacks are applied.
+pub fn sanitize(html: &str, policy: &SanitizePolicy) -> Vec<Content> {
+    let mut stack: Vec<OpenTag> = Vec::new();
+    let mut roots: Vec<Content> = Vec::new();
+
+    for token in tokenize(html) {
+        match token {
+            Token::Text(text) => attach(Content::Text(text), &mut stack, &mut roots),
+            Token::Start { name, attrs, self_closing } => {
+                let tag = ElementTag::from(name.as_str());
+                let allowed = policy.allowed_tags.contains(&tag);
+                let built_attrs = if allowed { build_attrs(&tag, attrs, policy) } else { Vec::new() };
+
+                if tag.is_void() || self_closing {
+                    if allowed {
+                        attach(Content::Element(apply_attrs(ContainerElement::new(tag), built_attrs)), &mut stack, &mut roots);
+                    }
+                    continue;
+                }
+
+                stack.push(OpenTag { name, tag, allowed, attrs: built_attrs, children: Vec::new() });
+            }
+            Token::End(name) => {
+                if let Some(pos) = stack.iter().rposition(|open| open.name == name) {
+                    while stack.len() > pos {
+                        let open = stack.pop().expect("stack.len() > pos implies non-empty");
+                        let content = close_tag(open);
+                        attach_all(content, &mut stack, &mut roots);
+                    }
+                }
+                // Stray end tag with no matching start: ignore, mirroring
+                // how browsers tolerate unbalanced markup.
+            }
+        }
+    }
+
+    while let Some(open) = stack.pop() {
+        let content = close_tag(open);
+        attach_all(content, &mut stack, &mut roots);
+    }
+
+    roots
+}
+
+/// A tag on the open-element stack, tracked from its start token until
+/// its matching (or implied) close.
+struct OpenTag {
+    name: String,
+    tag: ElementTag,
+    allowed: bool,
+    attrs: Vec<(String, String)>,
+    children: Vec<Content>,
+}
+
+/// Turns a closed tag into the content it contributes to its parent: the
+/// wrapped element if the tag was allowed, or just its children
+/// (flattened into the parent) if it was dropped.
+fn close_tag(open: OpenTag) -> Vec<Content> {
+    if open.allowed {
+        let element = apply_attrs(ContainerElement::new(open.tag), open.attrs).with_children(open.children);
+        vec![Content::Element(element)]
+    } else {
+        open.children
+    }
+}
+
+fn apply_attrs(mut element: ContainerElement, attrs: Vec<(String, String)>) -> ContainerElement {
+    for (key, value) in attrs {
+        element = match key.as_str() {
+            "id" => element.with_id(value),
+            "class" => value.split_whitespace().fold(element, |el, class| el.with_class(class)),
+            _ => element.with_attribute(key, value),
+        };
+    }
+    element
+}
+
+/// Filters `raw_attrs` down to what `policy` allows on `tag`, stripping
+/// unsafe URL schemes from `href`/`src` and applying any rewrite
+/// callback, in that order.
+fn build_attrs(tag: &ElementTag, raw_attrs: Vec<(String, String)>, policy: &SanitizePolicy) -> Vec<(String, String)> {
+    let allowed_names = policy.attrs_allowed_for(tag);
+    let mut result = Vec::new();
+
+    for (key, value) in raw_attrs {
+        if !allowed_names.contains(&key) {
+            continue;
+        }
+        if (key == "href" || key == "src") && !scheme_allowed(&value, &policy.allowed_schemes) {
+            continue;
+        }
+        match policy.rewrite_attrs.get(&key) {
+            Some(rewrite) => result.push(rewrite(&value)),
+            None => result.push((key, value)),
+        }
+    }
+
+    result
+}
+
+/// A URL with no scheme (a relative link) is always allowed; one with a
+/// scheme must have it in `allowed_schemes`.
+fn scheme_allowed(value: &str, allowed_schemes: &HashSet<String>) -> bool {
+    match url_scheme(value) {
+        Some(scheme) => allowed_schemes.contains(&scheme),
+        None => true,
+    }
+}
+
+/// Extracts the scheme from a URL (e.g. `"javascript"` from
+/// `"javascript:alert(1)"`), or `None` if `value` has no scheme prefix.
+///
+/// Strips every ASCII control character (tab, newline, CR, the rest of
+/// C0, and DEL) first, wherever it appears. That covers the tab and
+/// newline characters a browser's URL parser removes before it ever
+/// looks for a scheme — otherwise `"java\tscript:alert(1)"` reads as a
+/// scheme-less (and therefore always-allowed) relative URL instead of
+/// the `javascript:` URL it actually is once those controls are gone.
+fn url_scheme(value: &str) -> Option<String> {
+    let stripped: String = value.chars().filter(|c| !c.is_ascii_control()).collect();
+    let trimmed = stripped.trim();
+    let colon = trimmed.find(':')?;
+    let candidate = &trimmed[..colon];
+    if !candidate.is_empty() && candidate.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.') {
+        Some(candidate.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+fn attach(content: Content, stack: &mut [OpenTag], roots: &mut Vec<Content>) {
+    match stack.last_mut() {
+        Some(open) => open.children.push(content),
+        None => roots.push(content),
+    }
+}
+
+fn attach_all(contents: Vec<Content>, stack: &mut [OpenTag], roots: &mut Vec<Content>) {
+    for content in contents {
+        attach(content, stack, roots);
+    }
+}
+
+enum Token {
+    Start { name: String, attrs: Vec<(String, String)>, self_closing: bool },
+    End(String),
+    Text(String),
+}
+
+/// A minimal, tolerant HTML tokenizer: enough to recover tags,
+/// attributes and text from real-world rich text without pulling in a
+/// full HTML5 parser for what is, after all, about to be filtered down
+/// to an allowlist anyway.
+fn tokenize(html: &str) -> Vec<Token> {
+    let bytes = html.as_bytes();
+    let len = bytes.len();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut text_start = 0;
+
+    while i < len {
+        if bytes[i] != b'<' {
+            i += 1;
+            continue;
+        }
+
+        if i > text_start {
+            push_text(&html[text_start..i], &mut tokens);
+        }
+
+        if html[i..].starts_with("<!--") {
+            i += html[i..].find("-->").map(|end| end + 3).unwrap_or(len - i);
+            text_start = i;
+            continue;
+        }
+        if html[i..].starts_with("<!") || html[i..].starts_with("<?") {
+            i += html[i..].find('>').map(|end| end + 1).unwrap_or(len - i);
+            text_start = i;
+            continue;
+        }
+
+        let is_end = bytes.get(i + 1) == Some(&b'/');
+        let tag_start = if is_end { i + 2 } else { i + 1 };
+        let tag_end = find_tag_end(bytes, tag_start).unwrap_or(len);
+        let inner = &html[tag_start..tag_end.min(len)];
+
+        if is_end {
+            tokens.push(Token::End(inner.trim().to_ascii_lowercase()));
+        } else {
+            let self_closing = inner.trim_end().ends_with('/');
+            let inner = if self_closing { &inner[..inner.trim_end().len() - 1] } else { inner };
+            let (name, attrs) = parse_tag_inner(inner);
+            tokens.push(Token::Start { name, attrs, self_closing });
+        }
+
+        i = (tag_end + 1).min(len);
+        text_start = i;
+    }
+
+    if text_start < len {
+        push_text(&html[text_start..], &mut tokens);
+    }
+
+    tokens
+}
+
+fn push_text(text: &str, tokens: &mut Vec<Token>) {
+    let decoded = decode_entities(text);
+    if !decoded.is_empty() {
+        tokens.push(Token::Text(decoded));
+    }
+}
+
+/// Finds the `>` that closes a tag opened at `start`, treating `>`
+/// inside a quoted attribute value as plain text.
+fn find_tag_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut in_quote: Option<u8> = None;
+    for (offset, &b) in bytes[start..].iter().enumerate() {
+        match in_quote {
+            Some(quote) => {
+                if b == quote {
+                    in_quote = None;
+                }
+            }
+            None => {
+                if b == b'"' || b == b'\'' {
+                    in_quote = Some(b);
+                } else if b == b'>' {
+                    return Some(start + offset);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn parse_tag_inner(s: &str) -> (String, Vec<(String, String)>) {
+    let s = s.trim();
+    let name_end = s.find(|c: char| c.is_whitespace()).unwrap_or(s.len());
+    let name = s[..name_end].to_ascii_lowercase();
+    (name, parse_attrs(s[name_end..].trim_start()))
+}
+
+fn parse_attrs(s: &str) -> Vec<(String, String)> {
+    let bytes = s.as_bytes();
+    let len = bytes.len();
+    let mut attrs = Vec::new();
+    let mut i = 0;
+
+    while i < len {
+        while i < len && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let key_start = i;
+        while i < len && bytes[i] != b'=' && !bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i == key_start {
+            break;
+        }
+        let key = s[key_start..i].to_ascii_lowercase();
+
+        while i < len && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i < len && bytes[i] == b'=' {
+            i += 1;
+            while i < len && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            let value = if i < len && (bytes[i] == b'"' || bytes[i] == b'\'') {
+                let quote = bytes[i];
+                i += 1;
+                let value_start = i;
+                while i < len && bytes[i] != quote {
+                    i += 1;
+                }
+                let value = &s[value_start..i.min(len)];
+                i += 1;
+                value
+            } else {
+                let value_start = i;
+                while i < len && !bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                &s[value_start..i]
+            };
+            attrs.push((key, decode_entities(value)));
+        } else {
+            attrs.push((key, String::new()));
+        }
+    }
+
+    attrs
+}
+
+/// Decodes the handful of named entities and numeric character references
+/// that show up in hand-written or copy-pasted HTML.
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let Some(semi) = rest.find(';').filter(|&i| i <= 10) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{00A0}'),
+            _ => entity
+                .strip_prefix('#')
+                .and_then(|num| {
+                    num.strip_prefix('x')
+                        .or_else(|| num.strip_prefix('X'))
+                        .map(|hex| u32::from_str_radix(hex, 16).ok())
+                        .unwrap_or_else(|| num.parse().ok())
+                })
+                .and_then(char::from_u32),
+        };
+
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html_file::HtmlElement;
+
+    fn render_all(roots: &[Content]) -> String {
+        roots.iter().map(|content| content.render()).collect::<Vec<_>>().join("")
+    }
+
+    #[test]
+    fn test_disallowed_tag_dropped_but_text_kept() {
+        let roots = sanitize("<div>hello</div>", &SanitizePolicy::default());
+        assert_eq!(render_all(&roots), "<span>hello</span>");
+    }
+
+    #[test]
+    fn test_disallowed_attribute_stripped() {
+        let roots = sanitize(
+            r#"<a href="https://example.com" onclick="evil()">link</a>"#,
+            &SanitizePolicy::default(),
+        );
+        assert_eq!(render_all(&roots), r#"<a href="https://example.com"><span>link</span></a>"#);
+    }
+
+    #[test]
+    fn test_javascript_scheme_stripped() {
+        let roots = sanitize(r#"<a href="javascript:alert(1)">bad</a>"#, &SanitizePolicy::default());
+        assert_eq!(render_all(&roots), "<a><span>bad</span></a>");
+    }
+
+    #[test]
+    fn test_javascript_scheme_with_embedded_control_char_is_still_stripped() {
+        // A tab inside the scheme is stripped from URLs by browsers before
+        // parsing, so "java\tscript:" is still the javascript: scheme and
+        // must not slip through as a "relative, schemeless" URL.
+        let roots = sanitize("<a href=\"java\tscript:alert(1)\">bad</a>", &SanitizePolicy::default());
+        assert_eq!(render_all(&roots), "<a><span>bad</span></a>");
+    }
+
+    #[test]
+    fn test_rewrite_attr_renames_attribute() {
+        let policy = SanitizePolicy::empty()
+            .allow_tag(ElementTag::Img)
+            .allow_attr(ElementTag::Img, "src")
+            .allow_scheme("https")
+            .rewrite_attr("src", |value| ("data-src".to_string(), value.to_string()));
+
+        let roots = sanitize(r#"<img src="https://example.com/x.png">"#, &policy);
+        assert_eq!(render_all(&roots), r#"<img data-src="https://example.com/x.png" />"#);
+    }
+}
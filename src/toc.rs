@@ -0,0 +1,209 @@
+//! Builds a `<nav>`/`<ul>` table of contents from the heading elements in
+//! a body tree, assigning a stable `id` to any heading that doesn't
+//! already have one so the generated links have somewhere to point.
+
+use crate::html_file::{Content, ContainerElement, ElementTag};
+use std::collections::HashMap;
+
+/// Walks `elements` depth-first, assigns an `id` (slugified from the
+/// heading's text) to every `h1`-`h6` that lacks one, and returns a
+/// `<nav>` containing a nested `<ul>` of anchor links to each heading in
+/// document order.
+///
+/// Nesting mirrors heading level: a heading deeper than its predecessor
+/// opens a new `<ul>` inside the predecessor's `<li>`, and a heading at
+/// the same or a shallower level closes back out to the matching list.
+pub fn generate_toc(elements: &mut [Content]) -> Content {
+    let mut seen_ids = HashMap::new();
+    let mut headings = Vec::new();
+    collect_headings(elements, &mut seen_ids, &mut headings);
+
+    let list = if headings.is_empty() {
+        ContainerElement::new(ElementTag::Ul)
+    } else {
+        let mut index = 0;
+        build_list(&headings, &mut index)
+    };
+
+    Content::Element(ContainerElement::new(ElementTag::Nav).with_child(list))
+}
+
+/// Recursively walks `elements`, assigning ids to headings in place and
+/// appending `(level, id, text)` for each one found, in document order.
+fn collect_headings(
+    elements: &mut [Content],
+    seen_ids: &mut HashMap<String, usize>,
+    headings: &mut Vec<(u8, String, String)>,
+) {
+    for content in elements {
+        if let Content::Element(element) = content {
+            if let Some(level) = heading_level(element.tag_ref()) {
+                let text = element.text_content();
+                let id = match element.id_ref() {
+                    Some(id) => {
+                        let id = id.clone();
+                        seen_ids.entry(id.clone()).or_insert(1);
+                        id
+                    }
+                    None => {
+                        let id = unique_id(&slugify(&text), seen_ids);
+                        element.set_id(id.clone());
+                        id
+                    }
+                };
+                headings.push((level, id, text));
+            }
+            collect_headings(element.children_mut(), seen_ids, headings);
+        }
+    }
+}
+
+/// Returns the heading level (1-6) for `tag`, or `None` if it isn't a
+/// heading.
+fn heading_level(tag: &ElementTag) -> Option<u8> {
+    match tag {
+        ElementTag::H1 => Some(1),
+        ElementTag::H2 => Some(2),
+        ElementTag::H3 => Some(3),
+        ElementTag::H4 => Some(4),
+        ElementTag::H5 => Some(5),
+        ElementTag::H6 => Some(6),
+        _ => None,
+    }
+}
+
+/// Builds a `<ul>` out of `headings[*index..]`, consuming every entry at
+/// the level of `headings[*index]` or deeper, and recursing into a
+/// nested `<ul>` whenever a heading is deeper than the list it was found
+/// in. A jump of several levels (e.g. `h1` to `h3`) still opens a single
+/// nested `<ul>`. Returns once a heading shallower than this list's
+/// level is reached, leaving `*index` pointing at it.
+fn build_list(headings: &[(u8, String, String)], index: &mut usize) -> ContainerElement {
+    let level = headings[*index].0;
+    let mut list = ContainerElement::new(ElementTag::Ul);
+
+    while *index < headings.len() && headings[*index].0 >= level {
+        let (_, id, text) = &headings[*index];
+        let mut item = ContainerElement::new(ElementTag::Li).with_child(
+            ContainerElement::new(ElementTag::A)
+                .with_attribute("href", format!("#{}", id))
+                .with_text(text.clone()),
+        );
+        *index += 1;
+
+        if *index < headings.len() && headings[*index].0 > level {
+            item = item.with_child(build_list(headings, index));
+        }
+
+        list = list.with_child(item);
+    }
+
+    list
+}
+
+/// Turns `text` into a lowercase, hyphen-separated slug (non-alphanumeric
+/// characters dropped, runs of whitespace and hyphens collapsed to one
+/// hyphen), falling back to `"section"` when nothing is left.
+/// De-duplication is done separately by `unique_id`.
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    let mut pending_hyphen = false;
+
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            if pending_hyphen && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_hyphen = false;
+            slug.push(c.to_ascii_lowercase());
+        } else if c.is_whitespace() || c == '-' {
+            pending_hyphen = true;
+        }
+        // Other punctuation is stripped outright.
+    }
+
+    if slug.is_empty() {
+        slug.push_str("section");
+    }
+    slug
+}
+
+/// Returns `base`, or `base` suffixed with `-2`, `-3`, ... if it's already
+/// present in `seen_ids`, recording whichever id is returned.
+fn unique_id(base: &str, seen_ids: &mut HashMap<String, usize>) -> String {
+    let count = seen_ids.entry(base.to_string()).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+        base.to_string()
+    } else {
+        let id = format!("{}-{}", base, count);
+        seen_ids.entry(id.clone()).or_insert(1);
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html_file::HtmlElement;
+
+    #[test]
+    fn test_slugify_lowercases_and_hyphenates() {
+        assert_eq!(slugify("Hello World"), "hello-world");
+        assert_eq!(slugify("  Punctuation, Here!  "), "punctuation-here");
+        assert_eq!(slugify("Already-Hyphenated"), "already-hyphenated");
+        assert_eq!(slugify("!!!"), "section");
+    }
+
+    #[test]
+    fn test_unique_id_deduplicates_with_numeric_suffix() {
+        let mut seen = HashMap::new();
+        assert_eq!(unique_id("intro", &mut seen), "intro");
+        assert_eq!(unique_id("intro", &mut seen), "intro-2");
+        assert_eq!(unique_id("intro", &mut seen), "intro-3");
+    }
+
+    #[test]
+    fn test_generate_toc_assigns_ids_and_nests_by_level() {
+        let mut body = vec![
+            Content::Element(ContainerElement::new(ElementTag::H1).with_text("Intro")),
+            Content::Element(ContainerElement::new(ElementTag::H3).with_text("Deep Dive")),
+            Content::Element(ContainerElement::new(ElementTag::H1).with_text("Intro")),
+        ];
+
+        let nav = generate_toc(&mut body);
+
+        // Headings in the body get stable, de-duplicated ids in place.
+        let ids: Vec<&str> = body
+            .iter()
+            .map(|content| match content {
+                Content::Element(el) => el.id_ref().unwrap().as_str(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(ids, vec!["intro", "deep-dive", "intro-2"]);
+
+        let rendered = nav.render();
+        assert!(rendered.starts_with("<nav>"));
+        assert!(rendered.contains("<a href=\"#intro\"><span>Intro</span></a>"));
+        // The h3 nests inside the h1's <li> as a second <ul> level.
+        assert!(rendered.contains(
+            "<li><a href=\"#intro\"><span>Intro</span></a><ul><li><a href=\"#deep-dive\"><span>Deep Dive</span></a></li></ul></li>"
+        ));
+        assert!(rendered.contains("<a href=\"#intro-2\"><span>Intro</span></a>"));
+    }
+
+    #[test]
+    fn test_generate_toc_preserves_existing_id() {
+        let mut body = vec![Content::Element(
+            ContainerElement::new(ElementTag::H2).with_id("custom-id").with_text("Section"),
+        )];
+
+        generate_toc(&mut body);
+
+        match &body[0] {
+            Content::Element(el) => assert_eq!(el.id_ref().map(String::as_str), Some("custom-id")),
+            _ => unreachable!(),
+        }
+    }
+}